@@ -1,8 +1,10 @@
 use anyhow::Context;
-use clap::Parser;
-use n_puzzle::n_puzzle::NPuzzle;
+use clap::{Args, Parser, Subcommand};
+use n_puzzle::n_puzzle::{expansion_count, reset_expansion_count, Difficulty, NPuzzle};
+use n_puzzle::packed::{self, PackedBoard};
 use pathfinding::prelude::{astar, bfs, dfs, idastar, iddfs};
 use std::num::NonZeroU8;
+use std::time::Instant;
 
 /// Enum representing the available search algorithms.
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
@@ -19,12 +21,37 @@ enum SearchAlgorithm {
 enum Heuristic {
     NumIncorrect,
     Taxicab,
+    LinearConflict,
+}
+
+/// Which board type `bench` searches with: the `Matrix`-backed [`NPuzzle`] or the
+/// bit-packed [`PackedBoard`], so the two representations' speed can be compared directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Representation {
+    Matrix,
+    Packed,
 }
 
 /// Argument structure for use with the `clap` crate.
 #[derive(Parser, Debug)]
 #[command(about, long_about = None)]
 struct CliArgs {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Solve a puzzle given explicitly via `--pieces`/`--x-blank`/`--y-blank`.
+    Solve(SolveArgs),
+    /// Generate a random solvable puzzle and print its `--pieces`/`--x-blank`/`--y-blank`.
+    Generate(GenerateArgs),
+    /// Compare every algorithm/heuristic combination on one or more boards.
+    Bench(BenchArgs),
+}
+
+#[derive(Args, Debug)]
+struct SolveArgs {
     /// The search algorithm to use.
     #[arg(short, long, default_value = "a-star")]
     algorithm: SearchAlgorithm,
@@ -46,6 +73,56 @@ struct CliArgs {
     y_blank: usize,
 }
 
+#[derive(Args, Debug)]
+struct GenerateArgs {
+    /// The size of the puzzle to generate: 3 for an 8-puzzle, 4 for a 15-puzzle.
+    #[arg(short, long, default_value_t = 4)]
+    size: usize,
+
+    /// The number of random legal blank moves to scramble with; higher tends to be harder.
+    #[arg(short = 'n', long, default_value_t = 100)]
+    moves: usize,
+
+    /// Also classify the result as Easy/Medium/Hard by solving it with A*.
+    #[arg(short, long)]
+    rate: bool,
+}
+
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Pieces for a single, explicit board to benchmark. If omitted, random solvable
+    /// boards are generated instead, per `--size`/`--moves`/`--count`.
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_nonzero_u8)]
+    pieces: Option<Vec<NonZeroU8>>,
+
+    /// The x-coordinate of the blank position; required alongside `--pieces`.
+    #[arg(short, long, requires = "pieces")]
+    x_blank: Option<usize>,
+
+    /// The y-coordinate of the blank position; required alongside `--pieces`.
+    #[arg(short, long, requires = "pieces")]
+    y_blank: Option<usize>,
+
+    /// Size of the random boards to generate when `--pieces` is not given. Defaults to 3
+    /// rather than `generate`'s 4, since `BENCH_COMBOS` includes uninformed algorithms
+    /// (`Bfs`/`Dfs`) whose state space only stays tractable on the 8-puzzle.
+    #[arg(short, long, default_value_t = 3)]
+    size: usize,
+
+    /// Number of random legal moves used to scramble each generated board.
+    #[arg(short = 'n', long, default_value_t = 100)]
+    moves: usize,
+
+    /// Number of random boards to average results over when `--pieces` is not given.
+    #[arg(short, long, default_value_t = 1)]
+    count: usize,
+
+    /// Which board representation to search with -- `matrix` (the default `NPuzzle`) or
+    /// `packed` (the bit-packed `PackedBoard`), so the two can be compared head-to-head.
+    #[arg(long, default_value = "matrix")]
+    representation: Representation,
+}
+
 /// Parses a string into a `NonZeroU8`.
 fn parse_nonzero_u8(s: &str) -> anyhow::Result<NonZeroU8> {
     s.parse::<u8>()
@@ -59,6 +136,59 @@ fn main() -> anyhow::Result<()> {
     let args = CliArgs::parse();
     println!("{:?}", args);
 
+    match args.command {
+        Command::Solve(solve_args) => solve(solve_args),
+        Command::Generate(generate_args) => generate(generate_args),
+        Command::Bench(bench_args) => bench(bench_args),
+    }
+}
+
+/// Runs one search algorithm (with the given heuristic, which uninformed algorithms
+/// ignore) on `puzzle`, returning the solution path and its cost.
+fn run_search(
+    puzzle: &NPuzzle,
+    algorithm: SearchAlgorithm,
+    heuristic: Heuristic,
+) -> Option<(Vec<NPuzzle>, usize)> {
+    let heuristic_fn = match heuristic {
+        Heuristic::NumIncorrect => NPuzzle::num_incorrect,
+        Heuristic::Taxicab => NPuzzle::taxicab_distance,
+        Heuristic::LinearConflict => NPuzzle::linear_conflict,
+    };
+
+    match algorithm {
+        SearchAlgorithm::Bfs => bfs(puzzle, NPuzzle::successors, NPuzzle::success).map(|path| {
+            let cost = path.len() - 1;
+            (path, cost)
+        }),
+        SearchAlgorithm::Dfs => {
+            dfs(puzzle.clone(), NPuzzle::successors, NPuzzle::success).map(|path| {
+                let cost = path.len() - 1;
+                (path, cost)
+            })
+        }
+        SearchAlgorithm::IdDfs => {
+            iddfs(puzzle.clone(), NPuzzle::successors, NPuzzle::success).map(|path| {
+                let cost = path.len() - 1;
+                (path, cost)
+            })
+        }
+        SearchAlgorithm::AStar => astar(
+            puzzle,
+            NPuzzle::successors_with_costs,
+            heuristic_fn,
+            NPuzzle::success,
+        ),
+        SearchAlgorithm::IdAStar => idastar(
+            puzzle,
+            NPuzzle::successors_with_costs,
+            heuristic_fn,
+            NPuzzle::success,
+        ),
+    }
+}
+
+fn solve(args: SolveArgs) -> anyhow::Result<()> {
     let size = match args.pieces.len() {
         8 => 3,
         15 => 4,
@@ -81,45 +211,263 @@ fn main() -> anyhow::Result<()> {
     let puzzle =
         NPuzzle::new(size, args.pieces, blank_position).context("Failed to create puzzle")?;
 
-    let heuristic_fn = match args.heuristic {
-        Heuristic::NumIncorrect => NPuzzle::num_incorrect,
-        Heuristic::Taxicab => NPuzzle::taxicab_distance,
+    anyhow::ensure!(
+        puzzle.is_solvable(),
+        "This puzzle is not solvable:\n{puzzle}\nPass a solvable board via --pieces/--x-blank/--y-blank."
+    );
+
+    let (path, cost) = run_search(&puzzle, args.algorithm, args.heuristic).unwrap();
+    for node in path {
+        println!("{node}");
+    }
+    println!("This cost of this solution (the # of moves) was {cost}.");
+
+    Ok(())
+}
+
+fn generate(args: GenerateArgs) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        args.size == 3 || args.size == 4,
+        "Expected size to be 3 or 4, but got {}",
+        args.size
+    );
+
+    let mut rng = rand::thread_rng();
+    let puzzle = NPuzzle::random_solvable(args.size, args.moves, &mut rng);
+    let (pieces, (x_blank, y_blank)) = puzzle.pieces_and_blank();
+    let pieces_arg = pieces
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    println!("{puzzle}");
+    println!("--pieces {pieces_arg} --x-blank {x_blank} --y-blank {y_blank}");
+
+    if args.rate {
+        let (_, cost) = astar(
+            &puzzle,
+            NPuzzle::successors_with_costs,
+            NPuzzle::taxicab_distance,
+            NPuzzle::success,
+        )
+        .context("Generated puzzle was unexpectedly unsolvable")?;
+        println!(
+            "Difficulty: {} ({cost} moves to solve optimally)",
+            Difficulty::classify(args.size, cost)
+        );
+    }
+
+    Ok(())
+}
+
+/// One algorithm/heuristic pairing to run in `bench`. Uninformed algorithms ignore
+/// `heuristic`, but we still list one row per heuristic name so it's clear which pairings
+/// were actually exercised.
+const BENCH_COMBOS: &[(SearchAlgorithm, Heuristic)] = &[
+    (SearchAlgorithm::Bfs, Heuristic::NumIncorrect),
+    (SearchAlgorithm::Bfs, Heuristic::Taxicab),
+    (SearchAlgorithm::Bfs, Heuristic::LinearConflict),
+    (SearchAlgorithm::Dfs, Heuristic::NumIncorrect),
+    (SearchAlgorithm::Dfs, Heuristic::Taxicab),
+    (SearchAlgorithm::Dfs, Heuristic::LinearConflict),
+    (SearchAlgorithm::IdDfs, Heuristic::NumIncorrect),
+    (SearchAlgorithm::IdDfs, Heuristic::Taxicab),
+    (SearchAlgorithm::IdDfs, Heuristic::LinearConflict),
+    (SearchAlgorithm::AStar, Heuristic::NumIncorrect),
+    (SearchAlgorithm::AStar, Heuristic::Taxicab),
+    (SearchAlgorithm::AStar, Heuristic::LinearConflict),
+    (SearchAlgorithm::IdAStar, Heuristic::NumIncorrect),
+    (SearchAlgorithm::IdAStar, Heuristic::Taxicab),
+    (SearchAlgorithm::IdAStar, Heuristic::LinearConflict),
+];
+
+/// Runs one search algorithm (with the given heuristic, which uninformed algorithms
+/// ignore) on `board`, returning the solution path and its cost. Mirrors [`run_search`]
+/// but for the bit-packed [`PackedBoard`] representation.
+fn run_search_packed(
+    board: &PackedBoard,
+    algorithm: SearchAlgorithm,
+    heuristic: Heuristic,
+) -> Option<(Vec<PackedBoard>, usize)> {
+    let heuristic_fn = match heuristic {
+        Heuristic::NumIncorrect => PackedBoard::num_incorrect,
+        Heuristic::Taxicab => PackedBoard::taxicab_distance,
+        Heuristic::LinearConflict => PackedBoard::linear_conflict,
     };
 
-    let result = match args.algorithm {
-        SearchAlgorithm::Bfs => bfs(&puzzle, NPuzzle::successors, NPuzzle::success).map(|path| {
-            let cost = path.len() - 1;
-            (path, cost)
-        }),
-        SearchAlgorithm::Dfs => dfs(puzzle, NPuzzle::successors, NPuzzle::success).map(|path| {
-            let cost = path.len() - 1;
-            (path, cost)
-        }),
-        SearchAlgorithm::IdDfs => {
-            iddfs(puzzle, NPuzzle::successors, NPuzzle::success).map(|path| {
+    match algorithm {
+        SearchAlgorithm::Bfs => bfs(board, PackedBoard::successors, PackedBoard::success)
+            .map(|path| {
                 let cost = path.len() - 1;
                 (path, cost)
-            })
-        }
+            }),
+        SearchAlgorithm::Dfs => dfs(*board, PackedBoard::successors, PackedBoard::success)
+            .map(|path| {
+                let cost = path.len() - 1;
+                (path, cost)
+            }),
+        SearchAlgorithm::IdDfs => iddfs(*board, PackedBoard::successors, PackedBoard::success)
+            .map(|path| {
+                let cost = path.len() - 1;
+                (path, cost)
+            }),
         SearchAlgorithm::AStar => astar(
-            &puzzle,
-            NPuzzle::successors_with_costs,
+            board,
+            PackedBoard::successors_with_costs,
             heuristic_fn,
-            NPuzzle::success,
+            PackedBoard::success,
         ),
         SearchAlgorithm::IdAStar => idastar(
-            &puzzle,
-            NPuzzle::successors_with_costs,
+            board,
+            PackedBoard::successors_with_costs,
             heuristic_fn,
-            NPuzzle::success,
+            PackedBoard::success,
         ),
+    }
+}
+
+/// Prints the `bench` table for `boards` of the given `size`, running every combo in
+/// [`BENCH_COMBOS`] via `run`. Generic over the board representation so [`bench`] can
+/// drive it with either [`NPuzzle`] or [`PackedBoard`] and its matching expansion counter.
+///
+/// Uninformed combos (`Bfs`/`Dfs`/`IdDfs`) are skipped once `size` exceeds the 8-puzzle:
+/// their visited-set grows with the full state space, so against a 15-puzzle they never
+/// return, regardless of whether `boards` came from `--pieces` or a random size/count.
+fn print_bench_table<B>(
+    size: usize,
+    boards: &[B],
+    run: impl Fn(&B, SearchAlgorithm, Heuristic) -> Option<(Vec<B>, usize)>,
+    reset_expansion: impl Fn(),
+    read_expansion: impl Fn() -> usize,
+) {
+    if size > 3 {
+        println!("Skipping Bfs/Dfs/IdDfs: board size {size} exceeds the 8-puzzle, so their visited-set would never return.");
+    }
+    println!(
+        "{:<10} {:<15} {:>10} {:>12} {:>12}",
+        "algorithm", "heuristic", "avg length", "avg ms", "avg expanded"
+    );
+    let combos = BENCH_COMBOS.iter().filter(|(algorithm, _)| {
+        size <= 3
+            || !matches!(
+                algorithm,
+                SearchAlgorithm::Bfs | SearchAlgorithm::Dfs | SearchAlgorithm::IdDfs
+            )
+    });
+    for &(algorithm, heuristic) in combos {
+        let mut solved = 0usize;
+        let mut total_length = 0usize;
+        let mut total_millis = 0.0;
+        let mut total_expanded = 0usize;
+
+        for board in boards {
+            reset_expansion();
+            let start = Instant::now();
+            let result = run(board, algorithm, heuristic);
+            let elapsed = start.elapsed();
+
+            if let Some((_, cost)) = result {
+                solved += 1;
+                total_length += cost;
+                total_millis += elapsed.as_secs_f64() * 1000.0;
+                total_expanded += read_expansion();
+            }
+        }
+
+        if solved == 0 {
+            println!("{:<10?} {:<15?} {:>10}", algorithm, heuristic, "no solution");
+            continue;
+        }
+
+        println!(
+            "{:<10?} {:<15?} {:>10.1} {:>12.1} {:>12.1}",
+            algorithm,
+            heuristic,
+            total_length as f64 / solved as f64,
+            total_millis / solved as f64,
+            total_expanded as f64 / solved as f64,
+        );
+    }
+}
+
+fn bench(args: BenchArgs) -> anyhow::Result<()> {
+    let (size, boards) = match args.pieces {
+        Some(pieces) => {
+            let size = match pieces.len() {
+                8 => 3,
+                15 => 4,
+                _ => anyhow::bail!(
+                    "Expected 8 or 15 pieces, but got {}; pass pieces in via the --pieces flag",
+                    pieces.len()
+                ),
+            };
+            let x_blank = args
+                .x_blank
+                .context("--x-blank is required alongside --pieces")?;
+            let y_blank = args
+                .y_blank
+                .context("--y-blank is required alongside --pieces")?;
+
+            anyhow::ensure!(
+                x_blank < size,
+                "Expected x_blank to be less than {size}, but got {x_blank}; pass x_blank in via the --x-blank flag"
+            );
+            anyhow::ensure!(
+                y_blank < size,
+                "Expected y_blank to be less than {size}, but got {y_blank}; pass y_blank in via the --y-blank flag"
+            );
+
+            let board = NPuzzle::new(size, pieces, (x_blank, y_blank))
+                .context("Failed to create puzzle")?;
+            (size, vec![board])
+        }
+        None => {
+            anyhow::ensure!(
+                args.size == 3 || args.size == 4,
+                "Expected size to be 3 or 4, but got {}",
+                args.size
+            );
+            let mut rng = rand::thread_rng();
+            let boards = (0..args.count)
+                .map(|_| NPuzzle::random_solvable(args.size, args.moves, &mut rng))
+                .collect();
+            (args.size, boards)
+        }
     };
 
-    let (path, cost) = result.unwrap();
-    for node in path {
-        println!("{node}");
+    for board in &boards {
+        anyhow::ensure!(
+            board.is_solvable(),
+            "Board is not solvable:\n{board}\nPass a solvable board via --pieces/--x-blank/--y-blank."
+        );
+    }
+
+    match args.representation {
+        Representation::Matrix => print_bench_table(
+            size,
+            &boards,
+            run_search,
+            reset_expansion_count,
+            expansion_count,
+        ),
+        Representation::Packed => {
+            let packed_boards = boards
+                .iter()
+                .map(|board| {
+                    let (pieces, blank_position) = board.pieces_and_blank();
+                    PackedBoard::new(size, pieces, blank_position)
+                })
+                .collect::<Vec<_>>();
+            print_bench_table(
+                size,
+                &packed_boards,
+                run_search_packed,
+                packed::reset_expansion_count,
+                packed::expansion_count,
+            );
+        }
     }
-    println!("This cost of this solution (the # of moves) was {cost}.");
 
     Ok(())
 }
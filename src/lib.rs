@@ -0,0 +1,4 @@
+mod linear_conflict;
+pub mod n_puzzle;
+pub mod packed;
+mod zobrist;
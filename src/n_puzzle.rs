@@ -3,18 +3,59 @@ use pathfinding::{
     prelude::Weights,
 };
 
-use std::{fmt::Display, iter::once, num::NonZeroU8};
+use crate::zobrist;
+use std::{
+    fmt::Display,
+    hash::Hash,
+    iter::once,
+    num::NonZeroU8,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 pub type Board = Matrix<Option<NonZeroU8>>;
 
+/// Counts how many times [`NPuzzle::successors`] has expanded a state. Shared across the
+/// process so callers (e.g. the `bench` subcommand) can measure a search's node-expansion
+/// count without threading a counter through every `pathfinding` call. Reset it with
+/// [`reset_expansion_count`] before each run you want to measure independently.
+static EXPANSION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Resets the shared expansion counter kept by [`NPuzzle::successors`] to zero.
+pub fn reset_expansion_count() {
+    EXPANSION_COUNT.store(0, Ordering::Relaxed);
+}
+
+/// Reads the shared expansion counter kept by [`NPuzzle::successors`].
+pub fn expansion_count() -> usize {
+    EXPANSION_COUNT.load(Ordering::Relaxed)
+}
+
 pub type Pos = (usize, usize);
 
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
+#[derive(Debug, Clone)]
 pub struct NPuzzle {
     board: Board,
     blank_position: Pos,
+    /// Zobrist hash of `board`, kept up to date incrementally by [`Self::move_blank`]
+    /// instead of being recomputed from scratch on every lookup.
+    hash_key: u64,
+    /// This board's Zobrist table, shared (not re-fetched from the global cache) with
+    /// every `NPuzzle` of the same size derived from it, so [`Self::move_blank`] can XOR
+    /// in incremental hash updates without taking any lock.
+    zobrist_table: Arc<Vec<u64>>,
+}
+
+impl PartialEq for NPuzzle {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board && self.blank_position == other.blank_position
+    }
 }
 
+impl Eq for NPuzzle {}
+
 impl NPuzzle {
     pub fn new(
         size: usize,
@@ -34,14 +75,31 @@ impl NPuzzle {
         board_pieces.extend(board_items.take(size * size - blank_index - 1));
 
         let board = Matrix::square_from_vec(board_pieces)?;
+        let zobrist_table = zobrist::table(size);
+        let hash_key = board
+            .items()
+            .map(|(pos, value)| {
+                let tile = value.map_or(0, |v| v.get() as usize);
+                zobrist_table[zobrist::index(size, tile, Self::pos_index(pos, size))]
+            })
+            .fold(0, |key, tile_key| key ^ tile_key);
         Ok(NPuzzle {
             board,
             blank_position,
+            hash_key,
+            zobrist_table,
         })
     }
 
+    /// The row-major index of `pos` on a board of the given `size`, matching how `new`
+    /// lays tiles out and so also matching the indexing used by the Zobrist table.
+    fn pos_index(pos: Pos, size: usize) -> usize {
+        size * pos.0 + pos.1
+    }
+
     pub fn successors(&self) -> Vec<NPuzzle> {
         // println!("{self}");
+        EXPANSION_COUNT.fetch_add(1, Ordering::Relaxed);
         self.board
             // Get all the positions the blank can legally move to
             .neighbours(self.blank_position, false)
@@ -52,12 +110,68 @@ impl NPuzzle {
 
     pub fn move_blank(&self, new_blank_position: Pos) -> Self {
         assert_ne!(self.blank_position, new_blank_position);
+        let size = self.board.rows();
+        let moved_tile = self.board[new_blank_position]
+            .expect("new_blank_position must hold a tile, not the blank")
+            .get() as usize;
+        let old_blank_index = Self::pos_index(self.blank_position, size);
+        let new_blank_index = Self::pos_index(new_blank_position, size);
+
         let mut new_board = self.board.clone();
         new_board.swap(self.blank_position, new_blank_position);
+
+        // Only the blank and the tile it swapped with changed cells, so XOR out their old
+        // contributions and XOR in their new ones instead of rehashing the whole board. The
+        // table is just a field read -- no global lock is taken here, unlike `Self::new`.
+        let table = &self.zobrist_table;
+        let hash_key = self.hash_key
+            ^ table[zobrist::index(size, 0, old_blank_index)]
+            ^ table[zobrist::index(size, moved_tile, new_blank_index)]
+            ^ table[zobrist::index(size, 0, new_blank_index)]
+            ^ table[zobrist::index(size, moved_tile, old_blank_index)];
+
         NPuzzle {
             board: new_board,
             blank_position: new_blank_position,
+            hash_key,
+            zobrist_table: Arc::clone(&self.zobrist_table),
+        }
+    }
+
+    /// The raw Zobrist key for this board, exposed so callers can build their own
+    /// visited-state `HashSet`/`HashMap` keyed on it directly, without going through
+    /// `std::hash::Hash`.
+    pub fn hash_key(&self) -> u64 {
+        self.hash_key
+    }
+
+    /// The solved board of the given `size`: tiles `1..size*size` in row-major order,
+    /// with the blank in the last cell.
+    pub fn solved(size: usize) -> Self {
+        let pieces = (1..size * size).map(|v| NonZeroU8::new(v as u8).unwrap());
+        Self::new(size, pieces, (size - 1, size - 1)).expect("the solved board is always valid")
+    }
+
+    /// A guaranteed-solvable random scramble: starts from the solved board and applies
+    /// `num_moves` random legal blank moves, so the result is reachable from (and
+    /// therefore solvable back to) the goal by construction. Larger `num_moves` tends to
+    /// produce harder puzzles, though moves can cancel each other out.
+    pub fn random_solvable(size: usize, num_moves: usize, rng: &mut impl rand::Rng) -> Self {
+        let mut puzzle = Self::solved(size);
+        for _ in 0..num_moves {
+            let moves = puzzle.successors();
+            let index = rng.gen_range(0..moves.len());
+            puzzle = moves.into_iter().nth(index).expect("index is in range");
         }
+        puzzle
+    }
+
+    /// This puzzle's tiles in row-major order with the blank omitted, paired with the
+    /// blank's position -- the same shape `new` accepts, so a puzzle can be round-tripped
+    /// back into `--pieces`/`--x-blank`/`--y-blank` command-line arguments.
+    pub fn pieces_and_blank(&self) -> (Vec<NonZeroU8>, Pos) {
+        let pieces = self.board.items().filter_map(|(_, value)| *value).collect();
+        (pieces, self.blank_position)
     }
 
     pub fn successors_with_costs(&self) -> Vec<(NPuzzle, usize)> {
@@ -93,6 +207,55 @@ impl NPuzzle {
     pub fn success(&self) -> bool {
         self.num_incorrect() == 0
     }
+
+    /// Whether this board is reachable from (and therefore solvable back to) the goal
+    /// state, via the standard inversion-parity test: list the tile values in row-major
+    /// order with the blank omitted and count inversions (pairs `i < j` with
+    /// `value[i] > value[j]`). For an odd-sized board the puzzle is solvable iff that
+    /// count is even. For an even-sized board it's solvable iff `inversions + blank_row`
+    /// is odd, where `blank_row` is the blank's 1-based row counted from the bottom.
+    pub fn is_solvable(&self) -> bool {
+        let size = self.board.rows();
+        let values = self
+            .board
+            .items()
+            .filter_map(|(_, value)| value.map(|v| v.get() as usize))
+            .collect::<Vec<_>>();
+
+        let inversions = values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| values[i + 1..].iter().filter(|&&other| other < value).count())
+            .sum::<usize>();
+
+        if size % 2 == 1 {
+            inversions % 2 == 0
+        } else {
+            let blank_row_from_bottom = size - self.blank_position.0;
+            (inversions + blank_row_from_bottom) % 2 == 1
+        }
+    }
+
+    /// The Manhattan distance plus a linear-conflict penalty; see
+    /// [`crate::linear_conflict`] for how the penalty itself is computed. This stays
+    /// admissible while dominating `taxicab_distance`.
+    pub fn linear_conflict(&self) -> usize {
+        let size = self.board.rows();
+        let tiles = self
+            .board
+            .items()
+            .filter_map(|(pos, value)| value.map(|v| (pos, v.get() as usize - 1)))
+            .collect::<Vec<_>>();
+        self.taxicab_distance()
+            + crate::linear_conflict::line_conflicts(size, true, &tiles)
+            + crate::linear_conflict::line_conflicts(size, false, &tiles)
+    }
+}
+
+impl Hash for NPuzzle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash_key);
+    }
 }
 
 impl Display for NPuzzle {
@@ -110,6 +273,40 @@ impl Display for NPuzzle {
     }
 }
 
+/// A rough difficulty bucket for a generated puzzle, based on its optimal solution length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Classifies a puzzle of the given `size` by its optimal `solution_length`, scaling
+    /// the thresholds to the board's size so an 8-puzzle and a 15-puzzle land on a
+    /// comparable scale.
+    pub fn classify(size: usize, solution_length: usize) -> Self {
+        let unit = size * size;
+        if solution_length <= unit {
+            Difficulty::Easy
+        } else if solution_length <= unit * 2 {
+            Difficulty::Medium
+        } else {
+            Difficulty::Hard
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU8;
@@ -135,6 +332,37 @@ mod tests {
         assert_eq!(puzzle.taxicab_distance(), 8);
     }
 
+    #[test]
+    fn linear_conflict_at_least_taxicab() {
+        let puzzle = NPuzzle::new(
+            3,
+            [1, 3, 7, 2, 6, 5, 4, 8].map(|v| NonZeroU8::new(v).unwrap()),
+            (0, 0),
+        )
+        .unwrap();
+        assert!(puzzle.linear_conflict() >= puzzle.taxicab_distance());
+    }
+
+    #[test]
+    fn linear_conflict_when_solved() {
+        let puzzle = NPuzzle::new(4, (1..16).map(|v| NonZeroU8::new(v).unwrap()), (3, 3)).unwrap();
+        assert_eq!(puzzle.linear_conflict(), 0);
+    }
+
+    #[test]
+    fn linear_conflict_detects_reversed_row() {
+        // The top row holds 2 and 1 in that order, but their goal row is also the top row,
+        // so they're a linear conflict worth 2 on top of their Manhattan distance of 2.
+        let puzzle = NPuzzle::new(
+            3,
+            [2, 1, 3, 4, 5, 6, 7, 8].map(|v| NonZeroU8::new(v).unwrap()),
+            (2, 2),
+        )
+        .unwrap();
+        assert_eq!(puzzle.taxicab_distance(), 2);
+        assert_eq!(puzzle.linear_conflict(), 4);
+    }
+
     #[test]
     fn center_successors() {
         let puzzle = NPuzzle::new(
@@ -164,4 +392,83 @@ mod tests {
         assert!(successors.iter().any(|s| s.blank_position == (0, 1)));
         assert!(successors.iter().any(|s| s.blank_position == (1, 2)));
     }
+
+    #[test]
+    fn hash_key_matches_full_recompute_after_move() {
+        let puzzle = NPuzzle::new(
+            3,
+            [7, 8, 5, 3, 1, 4, 6, 2].map(|v| NonZeroU8::new(v).unwrap()),
+            (1, 1),
+        )
+        .unwrap();
+
+        for successor in puzzle.successors() {
+            let items = successor
+                .board
+                .items()
+                .filter_map(|(_, value)| *value)
+                .collect::<Vec<_>>();
+            let recomputed = NPuzzle::new(3, items, successor.blank_position).unwrap();
+            assert_eq!(successor.hash_key(), recomputed.hash_key());
+        }
+    }
+
+    #[test]
+    fn hash_key_differs_for_different_boards() {
+        let solved = NPuzzle::new(3, (1..9).map(|v| NonZeroU8::new(v).unwrap()), (2, 2)).unwrap();
+        let moved = solved.successors().into_iter().next().unwrap();
+        assert_ne!(solved.hash_key(), moved.hash_key());
+    }
+
+    #[test]
+    fn random_solvable_is_actually_solvable() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let puzzle = NPuzzle::random_solvable(3, 30, &mut rng);
+            let solution = pathfinding::prelude::bfs(&puzzle, NPuzzle::successors, NPuzzle::success);
+            assert!(solution.is_some());
+        }
+    }
+
+    #[test]
+    fn is_solvable_for_solved_and_scrambled_boards() {
+        let solved = NPuzzle::new(3, (1..9).map(|v| NonZeroU8::new(v).unwrap()), (2, 2)).unwrap();
+        assert!(solved.is_solvable());
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            assert!(NPuzzle::random_solvable(4, 30, &mut rng).is_solvable());
+        }
+    }
+
+    #[test]
+    fn is_solvable_rejects_a_single_swap() {
+        // Swapping any two tiles of a solved board is the classic unsolvable case.
+        let puzzle = NPuzzle::new(
+            3,
+            [2, 1, 3, 4, 5, 6, 7, 8].map(|v| NonZeroU8::new(v).unwrap()),
+            (2, 2),
+        )
+        .unwrap();
+        assert!(!puzzle.is_solvable());
+    }
+
+    #[test]
+    fn expansion_count_tracks_successor_calls() {
+        // The counter is a process-wide static, so other tests may bump it concurrently;
+        // assert on the delta from two calls rather than an absolute value.
+        let before = super::expansion_count();
+        let puzzle = NPuzzle::new(4, (1..16).map(|v| NonZeroU8::new(v).unwrap()), (3, 3)).unwrap();
+        puzzle.successors();
+        puzzle.successors();
+        assert!(super::expansion_count() >= before + 2);
+    }
+
+    #[test]
+    fn difficulty_classify_buckets_by_size() {
+        assert_eq!(super::Difficulty::classify(3, 0), super::Difficulty::Easy);
+        assert_eq!(super::Difficulty::classify(3, 9), super::Difficulty::Easy);
+        assert_eq!(super::Difficulty::classify(3, 15), super::Difficulty::Medium);
+        assert_eq!(super::Difficulty::classify(3, 25), super::Difficulty::Hard);
+    }
 }
@@ -0,0 +1,338 @@
+//! A bit-packed alternative board representation for
+//! [`NPuzzle`](crate::n_puzzle::NPuzzle).
+//!
+//! Each cell is a 4-bit nibble of a `u64`, holding the tile number (`0` for the blank,
+//! otherwise the tile's value). Sixteen nibbles fit exactly in a `u64`, which covers every
+//! size this crate solves (the 8-puzzle's 9 cells and the 15-puzzle's 16). Packing the
+//! whole board into one integer makes `move_blank` a couple of mask-and-shift operations,
+//! `success` a single integer comparison, and `Hash`/`Eq` a comparison of that one
+//! integer, instead of cloning and comparing a `Matrix` per node.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    num::NonZeroU8,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+pub type Pos = (usize, usize);
+
+/// Counts how many times [`PackedBoard::successors`] has expanded a state. Mirrors
+/// [`crate::n_puzzle::EXPANSION_COUNT`], kept separate so the `bench` subcommand can
+/// measure each board representation's node-expansion count independently.
+static EXPANSION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Resets the shared expansion counter kept by [`PackedBoard::successors`] to zero.
+pub fn reset_expansion_count() {
+    EXPANSION_COUNT.store(0, Ordering::Relaxed);
+}
+
+/// Reads the shared expansion counter kept by [`PackedBoard::successors`].
+pub fn expansion_count() -> usize {
+    EXPANSION_COUNT.load(Ordering::Relaxed)
+}
+
+const BITS_PER_TILE: u32 = 4;
+const TILE_MASK: u64 = 0xF;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PackedBoard {
+    size: usize,
+    bits: u64,
+    blank_index: usize,
+}
+
+impl PackedBoard {
+    pub fn new(
+        size: usize,
+        start_items: impl IntoIterator<Item = NonZeroU8>,
+        blank_position: Pos,
+    ) -> Self {
+        let blank_index = size * blank_position.0 + blank_position.1;
+        let mut items = start_items.into_iter();
+        let bits = (0..size * size).fold(0u64, |bits, index| {
+            let tile = if index == blank_index {
+                0
+            } else {
+                items
+                    .next()
+                    .expect("start_items must have one entry per non-blank cell")
+                    .get() as u64
+            };
+            bits | (tile << (index as u32 * BITS_PER_TILE))
+        });
+
+        PackedBoard {
+            size,
+            bits,
+            blank_index,
+        }
+    }
+
+    fn tile_at(&self, index: usize) -> u8 {
+        ((self.bits >> (index as u32 * BITS_PER_TILE)) & TILE_MASK) as u8
+    }
+
+    /// For each blank index on a board of the given `size`, the bitmask of indices the
+    /// blank could legally move to from there. Built lazily on first use; masks for every
+    /// `size` requested during the process's lifetime are cached, since a single run may
+    /// solve more than one puzzle size (e.g. the `bench` subcommand).
+    fn neighbour_mask(size: usize, blank_index: usize) -> u32 {
+        static TABLES: OnceLock<Mutex<HashMap<usize, Vec<u32>>>> = OnceLock::new();
+        let tables = TABLES.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut tables = tables.lock().unwrap();
+        let masks = tables.entry(size).or_insert_with(|| {
+            (0..size * size)
+                .map(|index| {
+                    let (row, col) = (index / size, index % size);
+                    let mut mask = 0u32;
+                    if row > 0 {
+                        mask |= 1 << (index - size);
+                    }
+                    if row + 1 < size {
+                        mask |= 1 << (index + size);
+                    }
+                    if col > 0 {
+                        mask |= 1 << (index - 1);
+                    }
+                    if col + 1 < size {
+                        mask |= 1 << (index + 1);
+                    }
+                    mask
+                })
+                .collect()
+        });
+        masks[blank_index]
+    }
+
+    /// The packed bits of the solved board of the given `size`, cached the same way as
+    /// [`Self::neighbour_mask`].
+    fn goal_bits(size: usize) -> u64 {
+        static TABLES: OnceLock<Mutex<HashMap<usize, u64>>> = OnceLock::new();
+        let tables = TABLES.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut tables = tables.lock().unwrap();
+        *tables.entry(size).or_insert_with(|| {
+            (1..size * size).fold(0u64, |bits, tile| {
+                bits | ((tile as u64) << ((tile - 1) as u32 * BITS_PER_TILE))
+            })
+        })
+    }
+
+    pub fn successors(&self) -> Vec<PackedBoard> {
+        EXPANSION_COUNT.fetch_add(1, Ordering::Relaxed);
+        let mask = Self::neighbour_mask(self.size, self.blank_index);
+        (0..self.size * self.size)
+            .filter(|index| mask & (1 << index) != 0)
+            .map(|new_blank_index| self.move_blank(new_blank_index))
+            .collect()
+    }
+
+    pub fn successors_with_costs(&self) -> Vec<(PackedBoard, usize)> {
+        self.successors().into_iter().map(|s| (s, 1)).collect()
+    }
+
+    pub fn move_blank(&self, new_blank_index: usize) -> Self {
+        assert_ne!(self.blank_index, new_blank_index);
+        let moved_tile = self.tile_at(new_blank_index) as u64;
+        let old_shift = self.blank_index as u32 * BITS_PER_TILE;
+        let new_shift = new_blank_index as u32 * BITS_PER_TILE;
+
+        // Clear both nibbles, then write the moved tile into the old blank's nibble --
+        // the new blank's nibble is left as 0.
+        let bits =
+            (self.bits & !(TILE_MASK << old_shift) & !(TILE_MASK << new_shift)) | (moved_tile << old_shift);
+
+        PackedBoard {
+            size: self.size,
+            bits,
+            blank_index: new_blank_index,
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.bits == Self::goal_bits(self.size)
+    }
+
+    pub fn num_incorrect(&self) -> usize {
+        (0..self.size * self.size)
+            .filter(|&index| {
+                let tile = self.tile_at(index);
+                tile != 0 && tile as usize != index + 1
+            })
+            .count()
+    }
+
+    pub fn taxicab_distance(&self) -> usize {
+        (0..self.size * self.size)
+            .filter_map(|index| {
+                let tile = self.tile_at(index);
+                (tile != 0).then(|| (index, tile as usize - 1))
+            })
+            .map(|(index, goal)| {
+                let (row, col) = (index / self.size, index % self.size);
+                let (goal_row, goal_col) = (goal / self.size, goal % self.size);
+                row.abs_diff(goal_row) + col.abs_diff(goal_col)
+            })
+            .sum()
+    }
+
+    /// The Manhattan distance plus a linear-conflict penalty, via the same shared
+    /// greedy per-line removal as
+    /// [`NPuzzle::linear_conflict`](crate::n_puzzle::NPuzzle::linear_conflict); see
+    /// [`crate::linear_conflict`] for how the penalty itself is computed.
+    pub fn linear_conflict(&self) -> usize {
+        let tiles = (0..self.size * self.size)
+            .filter_map(|index| {
+                let tile = self.tile_at(index);
+                (tile != 0).then(|| ((index / self.size, index % self.size), tile as usize - 1))
+            })
+            .collect::<Vec<_>>();
+        self.taxicab_distance()
+            + crate::linear_conflict::line_conflicts(self.size, true, &tiles)
+            + crate::linear_conflict::line_conflicts(self.size, false, &tiles)
+    }
+
+    /// Whether this board is reachable from the goal, via the same inversion-parity test
+    /// as [`NPuzzle::is_solvable`](crate::n_puzzle::NPuzzle::is_solvable).
+    pub fn is_solvable(&self) -> bool {
+        let values = (0..self.size * self.size)
+            .map(|index| self.tile_at(index))
+            .filter(|&tile| tile != 0)
+            .collect::<Vec<_>>();
+
+        let inversions = values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| values[i + 1..].iter().filter(|&&other| other < value).count())
+            .sum::<usize>();
+
+        if self.size % 2 == 1 {
+            inversions % 2 == 0
+        } else {
+            let blank_row_from_bottom = self.size - self.blank_index / self.size;
+            (inversions + blank_row_from_bottom) % 2 == 1
+        }
+    }
+}
+
+// Equality and hashing only ever need to compare the packed bits: within one search, every
+// `PackedBoard` shares the same `size`, and `blank_index` is redundant with `bits` (it's
+// just the index of the zero nibble).
+impl PartialEq for PackedBoard {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl Eq for PackedBoard {}
+
+impl Hash for PackedBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.bits);
+    }
+}
+
+impl Display for PackedBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.size {
+            for col in 0..self.size {
+                match self.tile_at(row * self.size + col) {
+                    0 => write!(f, "-- ")?,
+                    tile => write!(f, "{tile:>2} ")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU8;
+
+    use super::PackedBoard;
+
+    #[test]
+    fn heuristics_when_solved() {
+        let board = PackedBoard::new(4, (1..16).map(|v| NonZeroU8::new(v).unwrap()), (3, 3));
+        assert!(board.success());
+        assert_eq!(board.num_incorrect(), 0);
+        assert_eq!(board.taxicab_distance(), 0);
+    }
+
+    #[test]
+    fn heuristics_when_not_solved() {
+        let board = PackedBoard::new(
+            3,
+            [1, 3, 7, 2, 6, 5, 4, 8].map(|v| NonZeroU8::new(v).unwrap()),
+            (0, 0),
+        );
+        assert!(!board.success());
+        assert_eq!(board.num_incorrect(), 6);
+        assert_eq!(board.taxicab_distance(), 8);
+    }
+
+    #[test]
+    fn center_successors() {
+        let board = PackedBoard::new(
+            3,
+            [7, 8, 5, 3, 1, 4, 6, 2].map(|v| NonZeroU8::new(v).unwrap()),
+            (1, 1),
+        );
+        let successors = board.successors();
+        assert_eq!(successors.len(), 4);
+    }
+
+    #[test]
+    fn corner_successors() {
+        let board = PackedBoard::new(
+            3,
+            [7, 8, 5, 3, 1, 4, 6, 2].map(|v| NonZeroU8::new(v).unwrap()),
+            (0, 2),
+        );
+        assert_eq!(board.successors().len(), 2);
+    }
+
+    #[test]
+    fn move_blank_round_trips() {
+        let board = PackedBoard::new(3, (1..9).map(|v| NonZeroU8::new(v).unwrap()), (2, 2));
+        let moved = board.successors().into_iter().next().unwrap();
+        let back = moved.successors().into_iter().find(|b| *b == board);
+        assert_eq!(back, Some(board));
+    }
+
+    #[test]
+    fn linear_conflict_detects_reversed_row() {
+        // Same scenario as `NPuzzle`'s equivalent test: the top row holds 2 and 1 in that
+        // order, but their goal row is also the top row, so they're a linear conflict
+        // worth 2 on top of their Manhattan distance of 2.
+        let board = PackedBoard::new(
+            3,
+            [2, 1, 3, 4, 5, 6, 7, 8].map(|v| NonZeroU8::new(v).unwrap()),
+            (2, 2),
+        );
+        assert_eq!(board.taxicab_distance(), 2);
+        assert_eq!(board.linear_conflict(), 4);
+    }
+
+    #[test]
+    fn linear_conflict_when_solved() {
+        let board = PackedBoard::new(4, (1..16).map(|v| NonZeroU8::new(v).unwrap()), (3, 3));
+        assert_eq!(board.linear_conflict(), 0);
+    }
+
+    #[test]
+    fn is_solvable_rejects_a_single_swap() {
+        let board = PackedBoard::new(
+            3,
+            [2, 1, 3, 4, 5, 6, 7, 8].map(|v| NonZeroU8::new(v).unwrap()),
+            (2, 2),
+        );
+        assert!(!board.is_solvable());
+    }
+}
@@ -0,0 +1,69 @@
+//! Zobrist hashing for [`NPuzzle`](crate::n_puzzle::NPuzzle) boards.
+//!
+//! A `u64` table `z[tile][position]` (flattened to one `Vec<u64>`, indexed by
+//! `tile * cells + position`) is built lazily, the first time a board of a given `size` is
+//! constructed, and then shared via [`Arc`] with every [`NPuzzle`](crate::n_puzzle::NPuzzle)
+//! of that size for the rest of the process. `tile` 0 is reserved for the blank, so the
+//! table has `size * size` rows (one per tile, blank included) and `size * size` columns
+//! (one per board cell), matching the board's own indexing.
+//!
+//! Callers hold their own `Arc<Vec<u64>>` (see `NPuzzle`'s `zobrist_table` field) and index into it directly,
+//! so hashing a move costs a handful of array reads -- the global cache below is only ever
+//! touched once per board, in [`table`], not once per move.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+/// A small, fast, splittable PRNG used only to fill the Zobrist table. It is seeded with
+/// a fixed constant so the table (and therefore every hash derived from it) is the same
+/// across runs, which keeps results reproducible between program invocations.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Fixed seed for the Zobrist table, chosen arbitrarily; any constant works as long as
+/// it never changes, since changing it would only reshuffle hash values, not correctness.
+const SEED: u64 = 0x5350_555A_5A4C_4521;
+
+/// Builds the flattened `tile * cells + position` table for a board of the given `size`.
+/// The table is seeded from a fixed constant XORed with `size` itself, so tables for different
+/// sizes don't collide even though they're derived from the same PRNG.
+fn build_table(size: usize) -> Vec<u64> {
+    let cells = size * size;
+    let mut rng = SplitMix64(SEED ^ size as u64);
+    (0..cells * cells).map(|_| rng.next()).collect()
+}
+
+/// The Zobrist table for a board of the given `size`, as a flattened `tile * cells +
+/// position` slice shared behind an [`Arc`].
+///
+/// Tables are cached per `size` (built lazily, the first time that size is requested), and
+/// handed out as clones of the same `Arc` rather than copied, since a single run may solve
+/// more than one puzzle size (e.g. the `bench` subcommand). Callers are expected to hold
+/// onto the returned `Arc` (see `NPuzzle`'s `zobrist_table` field) and index into it directly, rather than
+/// calling back into this cache on every lookup.
+pub fn table(size: usize) -> Arc<Vec<u64>> {
+    static TABLES: OnceLock<Mutex<HashMap<usize, Arc<Vec<u64>>>>> = OnceLock::new();
+    let tables = TABLES.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut tables = tables.lock().unwrap();
+    tables
+        .entry(size)
+        .or_insert_with(|| Arc::new(build_table(size)))
+        .clone()
+}
+
+/// The flattened index of `(tile, position)` into a table built by [`table`] for this
+/// `size`, i.e. `tile * cells + position`.
+pub fn index(size: usize, tile: usize, position: usize) -> usize {
+    tile * size * size + position
+}